@@ -0,0 +1,139 @@
+//! # Suggestion cache
+//!
+//! Caches accepted commit message suggestions keyed by a hash of the
+//! normalized diff text, so re-running mkcmt against a staged diff that
+//! hasn't changed doesn't spend another API call. Backed by a `moka`
+//! in-memory cache that is loaded from, and persisted to, a small file
+//! under the repo's `.git` directory so it survives between invocations.
+
+use moka::sync::Cache;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const CACHE_FILE_NAME: &str = "mkcmt_suggestions.cache";
+const MAX_CAPACITY: u64 = 100;
+const TIME_TO_LIVE: Duration = Duration::from_secs(60 * 60 * 24 * 30);
+
+/// Caches accepted commit messages keyed by a hash of the diff they were generated from.
+pub struct SuggestionCache {
+    cache: Cache<u64, String>,
+    path: PathBuf,
+}
+
+impl SuggestionCache {
+    /// Opens the cache file under `git_dir`, loading any previously persisted entries.
+    pub fn open(git_dir: &Path) -> Self {
+        let path = git_dir.join(CACHE_FILE_NAME);
+        let cache = Cache::builder()
+            .max_capacity(MAX_CAPACITY)
+            .time_to_live(TIME_TO_LIVE)
+            .build();
+
+        if let Ok(file) = std::fs::File::open(&path) {
+            for line in BufReader::new(file).lines().map_while(Result::ok) {
+                if let Some((hash, message)) = line.split_once('\t') {
+                    if let Ok(hash) = hash.parse::<u64>() {
+                        cache.insert(hash, unescape(message));
+                    }
+                }
+            }
+        }
+
+        Self { cache, path }
+    }
+
+    /// Returns the cached suggestion for `diff_text`, if any.
+    pub fn get(&self, diff_text: &str) -> Option<String> {
+        self.cache.get(&hash_diff(diff_text))
+    }
+
+    /// Stores `message` as the accepted suggestion for `diff_text` and persists the cache to disk.
+    pub fn insert(&self, diff_text: &str, message: &str) -> std::io::Result<()> {
+        self.cache
+            .insert(hash_diff(diff_text), message.to_string());
+        self.persist()
+    }
+
+    fn persist(&self) -> std::io::Result<()> {
+        let mut file = std::fs::File::create(&self.path)?;
+        for (hash, message) in self.cache.iter() {
+            writeln!(file, "{}\t{}", hash, escape(&message))?;
+        }
+        Ok(())
+    }
+}
+
+/// Escapes backslashes, newlines and tabs so `message` round-trips through a
+/// single `hash\tmessage` line regardless of its contents. Backslash must be
+/// escaped first, or a literal `\n` substring in the message would be
+/// unescaped back into a newline on load.
+fn escape(message: &str) -> String {
+    message
+        .replace('\\', "\\\\")
+        .replace('\n', "\\n")
+        .replace('\t', "\\t")
+}
+
+/// Reverses [`escape`].
+fn unescape(message: &str) -> String {
+    let mut out = String::with_capacity(message.len());
+    let mut chars = message.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('\\') => out.push('\\'),
+            Some(other) => {
+                out.push('\\');
+                out.push(other);
+            }
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+/// Hashes `diff_text` after collapsing whitespace, so cosmetic differences
+/// (trailing newlines, line-ending noise) don't produce distinct cache keys.
+fn hash_diff(diff_text: &str) -> u64 {
+    let normalized = diff_text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let mut hasher = DefaultHasher::new();
+    normalized.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_diff_ignores_whitespace_differences() {
+        let a = "line one\nline two\n";
+        let b = "line   one\n\nline two";
+        assert_eq!(hash_diff(a), hash_diff(b));
+    }
+
+    #[test]
+    fn test_hash_diff_distinguishes_different_content() {
+        assert_ne!(hash_diff("+foo"), hash_diff("+bar"));
+    }
+
+    #[test]
+    fn test_escape_unescape_round_trip_newline_and_tab() {
+        let message = "feat: add thing\n\nBody with\ta tab and a literal \\n substring.";
+        assert_eq!(unescape(&escape(message)), message);
+    }
+
+    #[test]
+    fn test_escape_unescape_round_trip_backslash() {
+        let message = "path is C:\\temp\\file";
+        assert_eq!(unescape(&escape(message)), message);
+    }
+}
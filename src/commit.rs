@@ -14,6 +14,8 @@
 //! It also exposes a changelog module that models allowed commit types, scopes,
 //! and identifies breaking changes using the "!" marker.
 
+use crate::changelog::Changelog;
+
 /// The commit module contains types and parsing functionality for conventional commits.
     /// Represents a conventional commit message.
     #[derive(Debug, PartialEq, Eq)]
@@ -133,9 +135,9 @@
                 return Err("Empty commit message".to_string());
             }
 
-            // Parse header: expecting `<type>(<optional scope>): <description>`
+            // Parse header: expecting `<type>(<optional scope>)!: <description>`
             let header = parts[0].trim();
-            let (commit_type, scope, description) = Self::parse_header(header)?;
+            let (commit_type, scope, description, header_breaking) = Self::parse_header(header)?;
 
             // Parse optional body (if present)
             let body = if parts.len() > 1 && !parts[1].trim().is_empty() {
@@ -145,13 +147,14 @@
             };
 
             // Parse optional footer (if present) and check for breaking changes
-            let (footer, breaking) = if parts.len() > 2 && !parts[2].trim().is_empty() {
+            let (footer, footer_breaking) = if parts.len() > 2 && !parts[2].trim().is_empty() {
                 let footer_text = parts[2].trim().to_string();
                 let is_breaking = footer_text.contains('!');
                 (Some(footer_text), is_breaking)
             } else {
                 (None, false)
             };
+            let breaking = header_breaking || footer_breaking;
 
             Ok(CommitMessage {
                 commit_type,
@@ -165,13 +168,22 @@
 
         /// Helper function to parse the header line.
         ///
-        /// Returns a tuple of (commit_type, scope, description).
-        fn parse_header(header: &str) -> Result<(String, Option<String>, String), String> {
+        /// Returns a tuple of (commit_type, scope, description, breaking), where
+        /// `breaking` indicates a trailing `!` before the colon (e.g. `feat!:` or
+        /// `feat(core)!:`), the conventional-commit marker for a breaking change.
+        fn parse_header(header: &str) -> Result<(String, Option<String>, String, bool), String> {
             // Find the first colon that separates the header.
             let colon_index = header.find(':').ok_or("Missing ':' in header")?;
             let (meta, description) = header.split_at(colon_index);
             let description = description[1..].trim(); // skip the colon
 
+            // A trailing '!' right before the colon marks a breaking change.
+            let meta = meta.trim();
+            let (meta, breaking) = match meta.strip_suffix('!') {
+                Some(stripped) => (stripped.trim_end(), true),
+                None => (meta, false),
+            };
+
             // Check if there's an optional scope (enclosed in parentheses)
             if let Some(start) = meta.find('(') {
                 let end = meta.find(')').ok_or("Missing closing ')' in header")?;
@@ -180,12 +192,69 @@
                 if scope.is_empty() {
                     return Err("Empty scope in header".into());
                 }
-                Ok((commit_type, Some(scope), description.to_string()))
+                Ok((commit_type, Some(scope), description.to_string(), breaking))
             } else {
                 // No scope provided.
-                Ok((meta.trim().to_string(), None, description.to_string()))
+                Ok((meta.to_string(), None, description.to_string(), breaking))
+            }
+        }
+    }
+
+    /// Validates a commit message's header and footer against changelog metadata.
+    ///
+    /// Parses `msg` as a [`CommitMessage`] and checks that its `commit_type` is one
+    /// of `changelog.types`, that its optional `scope` (if present) is one of
+    /// `changelog.scopes`, and that a footer claiming a breaking change actually
+    /// uses `changelog.breaking_marker`. Returns the list of violations found, in
+    /// a form suitable for folding back into a refinement prompt; an empty vec
+    /// means `msg` is valid.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use mkcmt::commit::validate_commit;
+    /// use mkcmt::changelog::get_changelog;
+    ///
+    /// let violations = validate_commit("oops(ui): add new button", &get_changelog());
+    /// assert!(violations.iter().any(|v| v.contains("type 'oops' not allowed")));
+    /// ```
+    pub fn validate_commit(msg: &str, changelog: &Changelog) -> Vec<String> {
+        let commit = match CommitMessage::parse(msg) {
+            Ok(commit) => commit,
+            Err(err) => return vec![err],
+        };
+
+        let mut violations = Vec::new();
+
+        if !changelog.types.contains(&commit.commit_type.as_str()) {
+            violations.push(format!(
+                "type '{}' not allowed; use one of {}",
+                commit.commit_type,
+                changelog.types.join(", ")
+            ));
+        }
+
+        if let Some(scope) = &commit.scope {
+            if !changelog.scopes.contains(&scope.as_str()) {
+                violations.push(format!(
+                    "scope '{}' not allowed; use one of {}",
+                    scope,
+                    changelog.scopes.join(", ")
+                ));
+            }
+        }
+
+        if let Some(footer) = &commit.footer {
+            let claims_breaking = footer.to_uppercase().contains("BREAKING");
+            if claims_breaking && !footer.contains(changelog.breaking_marker) {
+                violations.push(format!(
+                    "breaking change footer must include the '{}' marker",
+                    changelog.breaking_marker
+                ));
             }
         }
+
+        violations
     }
 
     #[cfg(test)]
@@ -238,6 +307,44 @@ BREAKING CHANGE!: The button API has changed.";
             let err = CommitMessage::parse(input).unwrap_err();
             assert!(err.contains("Missing ':'"));
         }
+
+        #[test]
+        fn test_parse_breaking_marker_on_header_without_scope() {
+            let commit = CommitMessage::parse("feat!: drop X").unwrap();
+            assert_eq!(commit.commit_type, "feat");
+            assert_eq!(commit.scope, None);
+            assert!(commit.breaking);
+        }
+
+        #[test]
+        fn test_parse_breaking_marker_on_header_with_scope() {
+            let commit = CommitMessage::parse("feat(core)!: drop X").unwrap();
+            assert_eq!(commit.commit_type, "feat");
+            assert_eq!(commit.scope, Some("core".into()));
+            assert!(commit.breaking);
+        }
+
+        #[test]
+        fn test_validate_commit_accepts_known_type_and_scope() {
+            let changelog = crate::changelog::get_changelog();
+            let violations = validate_commit("feat(ui): add new button", &changelog);
+            assert!(violations.is_empty());
+        }
+
+        #[test]
+        fn test_validate_commit_accepts_breaking_header_marker() {
+            let changelog = crate::changelog::get_changelog();
+            let violations = validate_commit("feat!: drop X", &changelog);
+            assert!(violations.is_empty());
+        }
+
+        #[test]
+        fn test_validate_commit_rejects_unknown_type_and_scope() {
+            let changelog = crate::changelog::get_changelog();
+            let violations = validate_commit("oops(bogus): add new button", &changelog);
+            assert!(violations.iter().any(|v| v.contains("type 'oops'")));
+            assert!(violations.iter().any(|v| v.contains("scope 'bogus'")));
+        }
     }
 
 /// The changelog module contains data about allowed commit types, scopes, and the breaking change marker.
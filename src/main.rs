@@ -20,15 +20,34 @@ use arboard::Clipboard;
 #[cfg(all(feature = "uses_tokio_rt", feature = "uses_genai"))]
 use genai::Client;
 #[cfg(all(feature = "uses_tokio_rt", feature = "uses_genai"))]
-use genai::chat::{ChatMessage, ChatRequest};
+use genai::chat::{ChatMessage, ChatRequest, ChatStreamEvent};
+#[cfg(all(feature = "uses_tokio_rt", feature = "uses_genai"))]
+use futures::StreamExt;
 
-use std::fs::OpenOptions;
 use std::io::{self, Write};
-use std::process::Command;
 
 // --- CLI Argument Parsing ---
 use clap::Parser;
 
+#[cfg(all(feature = "uses_tokio_rt", feature = "uses_genai"))]
+use tracing::Instrument;
+
+mod repo;
+use repo::Repo;
+
+mod telemetry;
+
+mod preview;
+use preview::render_diff_preview;
+
+mod cache;
+use cache::SuggestionCache;
+
+#[cfg(all(feature = "uses_tokio_rt", feature = "uses_genai"))]
+use mkcmt::changelog::get_changelog;
+#[cfg(all(feature = "uses_tokio_rt", feature = "uses_genai"))]
+use mkcmt::commit::validate_commit;
+
 /// mkcmt: A tool for conventional commits and commit message recovery.
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
@@ -44,20 +63,18 @@ struct Args {
     /// Display the current (last) commit message.
     #[arg(short = 'c', long = "current")]
     current: bool,
-}
 
-// --- Common Helper Functions ---
+    /// Show a syntax-highlighted, colorized preview of the diff before querying the model.
+    #[arg(short = 'p', long)]
+    preview: bool,
 
-fn run_git_diff(args: &str) -> Result<String, Box<dyn std::error::Error>> {
-    let args_vec: Vec<&str> = if args.is_empty() {
-        vec!["diff"]
-    } else {
-        vec!["diff", args]
-    };
-    let output = Command::new("git").args(&args_vec).output()?;
-    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    /// Watch the working tree and index, suggesting a commit message whenever new changes are staged.
+    #[arg(short = 'w', long)]
+    watch: bool,
 }
 
+// --- Common Helper Functions ---
+
 fn confirm_user_input(prompt: &str) -> Result<bool, Box<dyn std::error::Error>> {
     print!("{}", prompt);
     io::stdout().flush()?;
@@ -66,19 +83,12 @@ fn confirm_user_input(prompt: &str) -> Result<bool, Box<dyn std::error::Error>>
     Ok(input.trim().eq_ignore_ascii_case("y"))
 }
 
-fn log_output(filename: &str, content: &str) -> Result<(), Box<dyn std::error::Error>> {
-    let mut file = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(filename)?;
-    writeln!(file, "\n----------------------\n{}\n", content)?;
-    Ok(())
-}
-
 /// Gather the Git diff text.
+#[tracing::instrument]
 fn get_diff_text() -> Result<String, Box<dyn std::error::Error>> {
-    let staged_diff_text = run_git_diff("--cached")?;
-    let unstaged_diff_text = run_git_diff("")?;
+    let repo = Repo::discover()?;
+    let staged_diff_text = repo.staged_diff()?;
+    let unstaged_diff_text = repo.unstaged_diff()?;
 
     let diff_text = if staged_diff_text.is_empty() {
         if unstaged_diff_text.is_empty() {
@@ -100,22 +110,107 @@ fn get_diff_text() -> Result<String, Box<dyn std::error::Error>> {
         staged_diff_text
     };
 
+    tracing::info!(diff_bytes = diff_text.len(), "gathered diff text");
     Ok(diff_text)
 }
 
+/// Opens the suggestion cache backed by the current repo's `.git` directory.
+fn open_cache() -> Result<SuggestionCache, Box<dyn std::error::Error>> {
+    let repo = Repo::discover()?;
+    Ok(SuggestionCache::open(repo.git_dir()))
+}
+
+/// Hashes `text` so the watch loop can cheaply tell whether the staged diff changed.
+#[cfg(feature = "uses_tokio_rt")]
+fn hash_text(text: &str) -> u64 {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    hasher.finish()
+}
+
+// --- Watch Mode ---
+
+/// Polls the repo's index for newly staged changes and runs the existing
+/// suggestion flow automatically each time the staged diff changes, turning
+/// mkcmt into a background assistant that proposes a commit message the
+/// moment you `git add`.
+#[cfg(feature = "uses_tokio_rt")]
+async fn run_watch_mode(cache: SuggestionCache) -> Result<(), Box<dyn std::error::Error>> {
+    let (tx, mut rx) = tokio::sync::mpsc::channel::<String>(8);
+
+    tokio::spawn(async move {
+        let mut last_seen: Option<u64> = None;
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(2));
+        loop {
+            interval.tick().await;
+
+            let Ok(repo) = Repo::discover() else {
+                continue;
+            };
+            let Ok(diff_text) = repo.staged_diff() else {
+                continue;
+            };
+            if diff_text.is_empty() {
+                continue;
+            }
+
+            let hash = hash_text(&diff_text);
+            if last_seen == Some(hash) {
+                continue;
+            }
+            last_seen = Some(hash);
+
+            if tx.send(diff_text).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    println!("Watching for staged changes (Ctrl+C to stop)...");
+    while let Some(diff_text) = rx.recv().await {
+        println!("\nDetected newly staged changes.");
+        if let Err(err) = chat_loop(diff_text, &cache).await {
+            tracing::warn!(error = %err, "suggestion flow failed; continuing to watch");
+            eprintln!("Suggestion flow failed: {err}. Still watching for staged changes.");
+        }
+    }
+
+    Ok(())
+}
+
 // --- Chat Loop Implementations ---
 
+/// Number of queries, counting the first one, after which changelog validation
+/// failures no longer trigger an automatic re-query and fall through to the
+/// manual accept/reject prompt (so at most `MAX_VALIDATION_ATTEMPTS - 1`
+/// automatic re-queries).
+#[cfg(all(feature = "uses_tokio_rt", feature = "uses_genai"))]
+const MAX_VALIDATION_ATTEMPTS: u32 = 3;
+
 // Asynchronous chat loop when using tokio_rt and GenAI.
 #[cfg(all(feature = "uses_tokio_rt", feature = "uses_genai"))]
-async fn chat_loop(diff_text: String) -> Result<(), Box<dyn std::error::Error>> {
+async fn chat_loop(
+    diff_text: String,
+    cache: &SuggestionCache,
+) -> Result<(), Box<dyn std::error::Error>> {
     let prompt_template =
         "Generate a conventional commit message referencing changed files:\n\n<GIT_DIFF>";
     let model = "gpt-4o-mini";
     let client = Client::default();
     let original_diff_text = diff_text.clone();
+    let changelog = get_changelog();
+    let mut violation_guidance = String::new();
+    let mut attempt: u32 = 0;
 
     loop {
-        let actual_prompt = prompt_template.replace("<GIT_DIFF>", &original_diff_text);
+        attempt += 1;
+        let actual_prompt = format!(
+            "{}{}",
+            prompt_template.replace("<GIT_DIFF>", &original_diff_text),
+            violation_guidance
+        );
 
         let chat_req = ChatRequest::new(vec![
             ChatMessage::system(
@@ -125,15 +220,39 @@ async fn chat_loop(diff_text: String) -> Result<(), Box<dyn std::error::Error>>
         ]);
 
         println!("\nQuerying ChatGPT for commit message...");
-        let chat_res = client.exec_chat(model, chat_req, None).await?;
-        let commit_message = chat_res
-            .content_text_as_str()
-            .unwrap_or("No response.")
-            .replace('`', "");
-
-        log_output("output_cc_suggestions.txt", &commit_message)?;
+        let round_trip_span = tracing::info_span!(
+            "llm_round_trip",
+            model,
+            diff_bytes = original_diff_text.len(),
+            attempt
+        );
+        let commit_message = stream_chat_response(&client, model, chat_req)
+            .instrument(round_trip_span)
+            .await?;
+
+        tracing::info!(attempt, suggestion = %commit_message, "received commit message suggestion");
         println!("\nSuggested commit message:\n{}", commit_message);
 
+        let violations = validate_commit(&commit_message, &changelog);
+        if !violations.is_empty() {
+            println!("\nGenerated message violates changelog rules:");
+            for violation in &violations {
+                println!("- {}", violation);
+            }
+            if attempt < MAX_VALIDATION_ATTEMPTS {
+                println!("Re-querying automatically to fix these issues...");
+                violation_guidance = format!(
+                    "\n\nThe previous suggestion was rejected for: {}. Fix these issues.",
+                    violations.join("; ")
+                );
+                continue;
+            }
+            println!(
+                "Giving up on automatic validation after {} attempts; falling back to manual review.",
+                MAX_VALIDATION_ATTEMPTS
+            );
+        }
+
         if confirm_user_input("\nAccept this commit message? (y/n): ")? {
             if confirm_user_input("\nCopy commit message to clipboard? (y/n): ")? {
                 #[cfg(feature = "uses_arboard")]
@@ -151,6 +270,7 @@ async fn chat_loop(diff_text: String) -> Result<(), Box<dyn std::error::Error>>
             } else {
                 println!("Commit message not copied.");
             }
+            cache.insert(&original_diff_text, &commit_message)?;
             break;
         } else {
             println!("Refining prompt for a better commit message...");
@@ -167,7 +287,7 @@ async fn chat_loop(diff_text: String) -> Result<(), Box<dyn std::error::Error>>
                 .unwrap_or(prompt_template)
                 .replace("<GIT_DIFF>", &original_diff_text);
 
-            log_output("output_cc_prompts.txt", &refined_prompt_template)?;
+            tracing::info!(attempt, refined_prompt = %refined_prompt_template, "refined prompt");
             println!("\nRefined prompt used:\n{}", refined_prompt_template);
         }
     }
@@ -175,16 +295,74 @@ async fn chat_loop(diff_text: String) -> Result<(), Box<dyn std::error::Error>>
     Ok(())
 }
 
+/// Streams a chat completion chunk-by-chunk over a bounded channel, printing
+/// each chunk as it arrives, and returns the accumulated text once the
+/// stream closes. The bounded channel gives natural backpressure so a slow
+/// terminal can't let chunks pile up unboundedly.
+#[cfg(all(feature = "uses_tokio_rt", feature = "uses_genai"))]
+#[tracing::instrument(skip(client, chat_req))]
+async fn stream_chat_response(
+    client: &Client,
+    model: &str,
+    chat_req: ChatRequest,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let chat_stream_res = client.exec_chat_stream(model, chat_req, None).await?;
+    let (tx, mut rx) = tokio::sync::mpsc::channel::<Result<String, String>>(32);
+
+    let mut stream = chat_stream_res.stream;
+    tokio::spawn(async move {
+        loop {
+            match stream.next().await {
+                Some(Ok(ChatStreamEvent::Chunk(chunk))) => {
+                    if tx.send(Ok(chunk.content)).await.is_err() {
+                        break;
+                    }
+                }
+                Some(Ok(_)) => {}
+                Some(Err(err)) => {
+                    let _ = tx.send(Err(err.to_string())).await;
+                    break;
+                }
+                None => break,
+            }
+        }
+    });
+
+    let mut commit_message = String::new();
+    while let Some(chunk) = rx.recv().await {
+        match chunk {
+            Ok(text) => {
+                print!("{}", text);
+                io::stdout().flush()?;
+                commit_message.push_str(&text);
+            }
+            Err(err) => {
+                println!();
+                return Err(format!("chat stream failed: {}", err).into());
+            }
+        }
+    }
+    println!();
+
+    Ok(commit_message.replace('`', ""))
+}
+
 // Asynchronous stub when using tokio_rt but GenAI is disabled.
 #[cfg(all(feature = "uses_tokio_rt", not(feature = "uses_genai")))]
-async fn chat_loop(_diff_text: String) -> Result<(), Box<dyn std::error::Error>> {
+async fn chat_loop(
+    _diff_text: String,
+    _cache: &SuggestionCache,
+) -> Result<(), Box<dyn std::error::Error>> {
     println!("Async chat functionality is disabled because the 'uses_genai' feature is off.");
     Ok(())
 }
 
 // Synchronous chat loop when using the plain Tokio dependency.
 #[cfg(feature = "uses_tokio_plain")]
-fn chat_loop(diff_text: String) -> Result<(), Box<dyn std::error::Error>> {
+fn chat_loop(
+    diff_text: String,
+    _cache: &SuggestionCache,
+) -> Result<(), Box<dyn std::error::Error>> {
     println!("Diff text:\n{}", diff_text);
     println!("Async chat functionality is disabled (using tokio_plain).");
     Ok(())
@@ -194,19 +372,12 @@ fn chat_loop(diff_text: String) -> Result<(), Box<dyn std::error::Error>> {
 
 /// Recovers the commit message from HEAD@{1}, prints it, and prompts the user to copy it to the clipboard.
 ///
-/// This function executes `git show -s --format=%B HEAD@{1}` to obtain the commit
-/// message, prints the message to the terminal, and then asks the user if they wish to copy it.
+/// This reads the commit message via the reflog instead of shelling out to
+/// `git show -s --format=%B HEAD@{1}`.
+#[tracing::instrument]
 fn recover_commit_message() -> Result<(), Box<dyn std::error::Error>> {
-    let output = Command::new("git")
-        .args(&["show", "-s", "--format=%B", "HEAD@{1}"])
-        .output()?;
-
-    if !output.status.success() {
-        eprintln!("Error retrieving commit message");
-        std::process::exit(1);
-    }
-
-    let commit_message = String::from_utf8_lossy(&output.stdout);
+    let repo = Repo::discover()?;
+    let commit_message = repo.reflog_message(1)?;
     println!("Recovered commit message:\n\n{}", commit_message);
 
     if confirm_user_input("\nCopy commit message to clipboard? (y/n): ")? {
@@ -222,32 +393,19 @@ fn recover_commit_message() -> Result<(), Box<dyn std::error::Error>> {
 // --- New: Soft Reset Mode ---
 
 /// Performs a soft reset on the current branch.
+#[tracing::instrument]
 fn perform_soft_reset() -> Result<(), Box<dyn std::error::Error>> {
-    let output = Command::new("git")
-        .args(&["reset", "--soft", "HEAD~1"])
-        .output()?;
-
-    if !output.status.success() {
-        eprintln!("Error performing soft reset");
-        std::process::exit(1);
-    }
-
+    let repo = Repo::discover()?;
+    repo.soft_reset_head(1)?;
     println!("Soft reset performed on the current branch.");
     Ok(())
 }
 
 /// Displays the current (last) commit message.
+#[tracing::instrument]
 fn display_last_commit_message() -> Result<(), Box<dyn std::error::Error>> {
-    let output = Command::new("git")
-        .args(&["show", "-s", "--format=%B", "HEAD"])
-        .output()?;
-
-    if !output.status.success() {
-        eprintln!("Error retrieving last commit message");
-        std::process::exit(1);
-    }
-
-    let commit_message = String::from_utf8_lossy(&output.stdout);
+    let repo = Repo::discover()?;
+    let commit_message = repo.head_message()?;
     println!("Last commit message:\n\n{}", commit_message);
     Ok(())
 }
@@ -257,6 +415,7 @@ fn display_last_commit_message() -> Result<(), Box<dyn std::error::Error>> {
 // When using tokio_rt, parse CLI arguments, check for additional flags, then run the async main.
 #[cfg(feature = "uses_tokio_rt")]
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    telemetry::init();
     let args = Args::parse();
 
     if args.recovery {
@@ -268,24 +427,43 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     } else if args.current {
         display_last_commit_message()?;
         return Ok(());
+    } else if args.watch {
+        let cache = open_cache()?;
+        let rt = tokio::runtime::Runtime::new()?;
+        return rt.block_on(run_watch_mode(cache));
     }
 
     let diff_text = get_diff_text()?;
     if diff_text.is_empty() {
         return Ok(());
     }
+    if args.preview {
+        println!("{}", render_diff_preview(&diff_text));
+    }
+    let cache = open_cache()?;
+    if let Some(cached_message) = cache.get(&diff_text) {
+        println!("\nCached suggestion:\n{}", cached_message);
+        if confirm_user_input("\nReuse cached suggestion? (y/n): ")? {
+            println!("\nUsing cached commit message:\n{}", cached_message);
+            return Ok(());
+        }
+    }
     let rt = tokio::runtime::Runtime::new()?;
-    rt.block_on(async_main(diff_text))
+    rt.block_on(async_main(diff_text, cache))
 }
 
 #[cfg(feature = "uses_tokio_rt")]
-async fn async_main(diff_text: String) -> Result<(), Box<dyn std::error::Error>> {
-    chat_loop(diff_text).await
+async fn async_main(
+    diff_text: String,
+    cache: SuggestionCache,
+) -> Result<(), Box<dyn std::error::Error>> {
+    chat_loop(diff_text, &cache).await
 }
 
 // When using tokio_plain, parse CLI arguments and check for additional flags, then run synchronously.
 #[cfg(feature = "uses_tokio_plain")]
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    telemetry::init();
     let args = Args::parse();
 
     if args.recovery {
@@ -297,13 +475,27 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     } else if args.current {
         display_last_commit_message()?;
         return Ok(());
+    } else if args.watch {
+        println!("Watch mode requires the 'uses_tokio_rt' feature; it is disabled under tokio_plain.");
+        return Ok(());
     }
 
     let diff_text = get_diff_text()?;
     if diff_text.is_empty() {
         return Ok(());
     }
-    chat_loop(diff_text)
+    if args.preview {
+        println!("{}", render_diff_preview(&diff_text));
+    }
+    let cache = open_cache()?;
+    if let Some(cached_message) = cache.get(&diff_text) {
+        println!("\nCached suggestion:\n{}", cached_message);
+        if confirm_user_input("\nReuse cached suggestion? (y/n): ")? {
+            println!("\nUsing cached commit message:\n{}", cached_message);
+            return Ok(());
+        }
+    }
+    chat_loop(diff_text, &cache)
 }
 
 // #![doc = include_str!("../README.md")]
@@ -0,0 +1,107 @@
+//! # Diff preview
+//!
+//! Renders a unified diff as a syntax-highlighted, colorized preview so the
+//! user can review exactly what is about to be sent to the model before an
+//! API call is spent on it. Each hunk's language is detected from the file
+//! path in its `diff --git` / `+++` header; context and hunk-header lines are
+//! highlighted with `syntect` and emitted as ANSI escape sequences rather than
+//! HTML, while added and removed lines are colored green and red as a whole
+//! without syntax highlighting.
+
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{Style, ThemeSet};
+use syntect::parsing::{SyntaxReference, SyntaxSet};
+use syntect::util::as_24_bit_terminal_escaped;
+
+const RESET: &str = "\x1b[0m";
+const GREEN: &str = "\x1b[32m";
+const RED: &str = "\x1b[31m";
+
+/// Renders `diff_text` as a colorized, syntax-highlighted preview suitable
+/// for printing to a terminal.
+pub fn render_diff_preview(diff_text: &str) -> String {
+    let syntax_set = SyntaxSet::load_defaults_newlines();
+    let theme_set = ThemeSet::load_defaults();
+    let theme = &theme_set.themes["base16-ocean.dark"];
+
+    let mut out = String::new();
+    let mut syntax: &SyntaxReference = syntax_set.find_syntax_plain_text();
+    let mut highlighter = HighlightLines::new(syntax, theme);
+
+    for line in diff_text.lines() {
+        if let Some(path) = file_path_from_header(line) {
+            syntax = syntax_set
+                .find_syntax_for_file(path)
+                .ok()
+                .flatten()
+                .unwrap_or_else(|| syntax_set.find_syntax_plain_text());
+            highlighter = HighlightLines::new(syntax, theme);
+            out.push_str(line);
+            out.push('\n');
+            continue;
+        }
+
+        // Relies on `repo::diff_to_patch_text` prefixing every added/removed/context
+        // line with its git2 `line.origin()` character, matching unified diff
+        // format; without that prefix no line here is ever colored.
+        let (prefix_color, body) = match line.as_bytes().first() {
+            Some(b'+') if !line.starts_with("+++") => (Some(GREEN), line),
+            Some(b'-') if !line.starts_with("---") => (Some(RED), line),
+            _ => (None, line),
+        };
+
+        match prefix_color {
+            Some(color) => {
+                out.push_str(color);
+                out.push_str(body);
+                out.push_str(RESET);
+                out.push('\n');
+            }
+            None => {
+                let ranges: Vec<(Style, &str)> =
+                    highlighter.highlight_line(line, &syntax_set).unwrap_or_default();
+                out.push_str(&as_24_bit_terminal_escaped(&ranges[..], true));
+                out.push_str(RESET);
+                out.push('\n');
+            }
+        }
+    }
+
+    out
+}
+
+/// Extracts the `b/`-side file path from a `diff --git` or `+++` header line,
+/// or `None` if `line` isn't such a header.
+fn file_path_from_header(line: &str) -> Option<&str> {
+    if let Some(rest) = line.strip_prefix("+++ b/") {
+        return Some(rest);
+    }
+    if let Some(rest) = line.strip_prefix("diff --git a/") {
+        return rest.split(" b/").nth(1);
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_path_from_plus_plus_plus_header() {
+        assert_eq!(file_path_from_header("+++ b/src/main.rs"), Some("src/main.rs"));
+    }
+
+    #[test]
+    fn test_file_path_from_diff_git_header() {
+        assert_eq!(
+            file_path_from_header("diff --git a/src/main.rs b/src/main.rs"),
+            Some("src/main.rs")
+        );
+    }
+
+    #[test]
+    fn test_file_path_from_header_ignores_non_header_lines() {
+        assert_eq!(file_path_from_header("+some added line"), None);
+        assert_eq!(file_path_from_header("-+++ not a header"), None);
+    }
+}
@@ -0,0 +1,133 @@
+//! # Repository access
+//!
+//! Wraps the repository in a single `git2::Repository` handle opened once at
+//! startup, and exposes typed helpers for the operations mkcmt needs. This
+//! replaces shelling out to a `git` binary on `PATH` and parsing its stdout,
+//! so callers get real error types and keep working even when the current
+//! directory is a subdirectory of the repo.
+
+use git2::{Diff, DiffFormat, DiffOptions, Repository, ResetType};
+use std::path::Path;
+
+/// A thin wrapper around an open `git2::Repository`.
+pub struct Repo {
+    inner: Repository,
+}
+
+impl Repo {
+    /// Opens the repository, searching upward from the current directory the
+    /// way `git` itself does.
+    pub fn discover() -> Result<Self, git2::Error> {
+        Ok(Self {
+            inner: Repository::discover(".")?,
+        })
+    }
+
+    /// Returns the diff between `HEAD` and the index (i.e. staged changes) as patch text.
+    pub fn staged_diff(&self) -> Result<String, git2::Error> {
+        let head_tree = self.inner.head().ok().and_then(|h| h.peel_to_tree().ok());
+        let diff = self
+            .inner
+            .diff_tree_to_index(head_tree.as_ref(), None, None)?;
+        diff_to_patch_text(&diff)
+    }
+
+    /// Returns the diff between the index and the working tree (i.e. unstaged changes) as patch text.
+    pub fn unstaged_diff(&self) -> Result<String, git2::Error> {
+        let mut opts = DiffOptions::new();
+        let diff = self.inner.diff_index_to_workdir(None, Some(&mut opts))?;
+        diff_to_patch_text(&diff)
+    }
+
+    /// Reads the current `HEAD` commit message directly, equivalent to
+    /// `git show -s --format=%B HEAD`. Unlike [`Repo::reflog_message`], this
+    /// doesn't depend on the `HEAD` reflog being present or enabled.
+    pub fn head_message(&self) -> Result<String, git2::Error> {
+        let commit = self.inner.head()?.peel_to_commit()?;
+        Ok(commit.message().unwrap_or_default().to_string())
+    }
+
+    /// Reads the commit message at `HEAD@{n}` via the reflog, equivalent to
+    /// `git show -s --format=%B HEAD@{n}`.
+    pub fn reflog_message(&self, n: usize) -> Result<String, git2::Error> {
+        let reflog = self.inner.reflog("HEAD")?;
+        let entry = reflog
+            .get(n)
+            .ok_or_else(|| git2::Error::from_str(&format!("no reflog entry HEAD@{{{n}}}")))?;
+        let commit = self.inner.find_commit(entry.id_new())?;
+        Ok(commit.message().unwrap_or_default().to_string())
+    }
+
+    /// Returns the path to the repository's `.git` directory.
+    pub fn git_dir(&self) -> &Path {
+        self.inner.path()
+    }
+
+    /// Soft-resets `HEAD` back `n` commits, equivalent to `git reset --soft HEAD~n`.
+    pub fn soft_reset_head(&self, n: usize) -> Result<(), git2::Error> {
+        let mut target = self.inner.head()?.peel_to_commit()?;
+        for _ in 0..n {
+            target = target.parent(0)?;
+        }
+        self.inner.reset(target.as_object(), ResetType::Soft, None)?;
+        Ok(())
+    }
+}
+
+fn diff_to_patch_text(diff: &Diff) -> Result<String, git2::Error> {
+    let mut patch = String::new();
+    diff.print(DiffFormat::Patch, |_delta, _hunk, line| {
+        match line.origin() {
+            '+' | '-' | ' ' => patch.push(line.origin()),
+            _ => {}
+        }
+        if let Ok(text) = std::str::from_utf8(line.content()) {
+            patch.push_str(text);
+        }
+        true
+    })?;
+    Ok(patch.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    /// Creates a throwaway repo under the system temp dir with `file_name`
+    /// staged at `contents`, and returns its `diff_tree_to_index` patch text.
+    fn staged_patch_for(file_name: &str, contents: &str) -> String {
+        let dir = std::env::temp_dir().join(format!(
+            "mkcmt-repo-test-{}-{}",
+            std::process::id(),
+            file_name.replace('/', "_")
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let repo = Repository::init(&dir).unwrap();
+        fs::write(dir.join(file_name), contents).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new(file_name)).unwrap();
+        index.write().unwrap();
+
+        let diff = repo.diff_tree_to_index(None, None, None).unwrap();
+        let patch = diff_to_patch_text(&diff).unwrap();
+
+        fs::remove_dir_all(&dir).unwrap();
+        patch
+    }
+
+    #[test]
+    fn test_diff_to_patch_text_prefixes_added_lines_with_plus() {
+        let patch = staged_patch_for("added.txt", "hello\nworld\n");
+        assert!(
+            patch.lines().any(|line| line == "+hello"),
+            "expected a '+hello' line in:\n{patch}"
+        );
+        assert!(
+            patch.lines().any(|line| line == "+world"),
+            "expected a '+world' line in:\n{patch}"
+        );
+    }
+}
@@ -0,0 +1,74 @@
+//! # Telemetry
+//!
+//! Replaces ad-hoc file logging with structured `tracing` spans and events,
+//! so git operations and LLM round-trips can be filtered and correlated
+//! instead of grepped out of a flat text file. `RUST_LOG` controls verbosity
+//! via `EnvFilter`, the same way the tokio examples wire it up. A file layer
+//! persists the same events that used to go to `output_cc_suggestions.txt` /
+//! `output_cc_prompts.txt`, so users can raise verbosity without touching the
+//! code. Both layers write the human-readable text format; there is no JSON
+//! output.
+
+use std::fs::{File, OpenOptions};
+use std::path::PathBuf;
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::{EnvFilter, fmt, prelude::*};
+
+use crate::repo::Repo;
+
+const LOG_FILE_NAME: &str = "mkcmt.log";
+
+/// Installs the global tracing subscriber: a human-readable stdout layer
+/// filtered by `RUST_LOG`, plus (best-effort) a file layer that persists the
+/// same events (including span open/close timing) to `mkcmt.log` under the
+/// repo's `.git` directory. If the log file can't be opened, the file layer
+/// is skipped rather than aborting the whole process, so read-only
+/// invocations (`--current`, `--recovery`) still work in directories mkcmt
+/// can't write to.
+pub fn init() {
+    let env_filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("mkcmt=info"));
+
+    let stdout_layer = fmt::layer()
+        .with_target(false)
+        .with_span_events(FmtSpan::CLOSE);
+
+    let file_layer = open_log_file().map(|log_file| {
+        fmt::layer()
+            .with_ansi(false)
+            .with_target(false)
+            .with_span_events(FmtSpan::CLOSE)
+            .with_writer(log_file)
+    });
+
+    tracing_subscriber::registry()
+        .with(env_filter)
+        .with(stdout_layer)
+        .with(file_layer)
+        .init();
+}
+
+/// Opens the structured-log file under the repo's `.git` directory, falling
+/// back to the current directory if the repo can't be discovered. Returns
+/// `None` (instead of panicking) if the file can't be opened, logging a
+/// warning to stderr.
+fn open_log_file() -> Option<File> {
+    let log_dir = Repo::discover()
+        .map(|repo| repo.git_dir().to_path_buf())
+        .unwrap_or_else(|_| PathBuf::from("."));
+
+    match OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(log_dir.join(LOG_FILE_NAME))
+    {
+        Ok(file) => Some(file),
+        Err(err) => {
+            eprintln!(
+                "warning: could not open {} for structured logging: {}",
+                LOG_FILE_NAME, err
+            );
+            None
+        }
+    }
+}